@@ -0,0 +1,90 @@
+//! Derive macro for `bit_set::bitidx::BitSet` element types
+//!
+//! Hand-writing `Into<BitIdx>`/`From<BitIdx>` for a C-like enum is
+//! boilerplate, and the `From<BitIdx>` side almost always ends up as a
+//! `match` with a `panic!` fallback arm for indices that don't correspond
+//! to a variant. `#[derive(BitIdxEnum)]` generates both impls from the
+//! enum's variant list, plus a `bit_set::bitidx::TryFromBitIdx` impl so
+//! `BitSet::try_iter` can reconstruct a variant from an index and fail
+//! safely instead of aborting.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bit_set_derive::BitIdxEnum;
+//!
+//! #[derive(Debug, Clone, Copy, BitIdxEnum)]
+//! enum Foo { A, B, C }
+//! ```
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+#[proc_macro_derive(BitIdxEnum)]
+pub fn derive_bit_idx_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let variants = match input.data {
+        Data::Enum(ref data) => &data.variants,
+        _ => panic!("#[derive(BitIdxEnum)] can only be applied to field-less enums"),
+    };
+
+    let idents: Vec<_> = variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                panic!("#[derive(BitIdxEnum)] can only be applied to field-less enums");
+            }
+            if variant.discriminant.is_some() {
+                // `Into<BitIdx>` below uses the real discriminant
+                // (`self as usize`), while `try_from_bit_idx`/`From<BitIdx>`
+                // match on declaration-order position; an explicit
+                // discriminant would make the two silently disagree.
+                panic!("#[derive(BitIdxEnum)] does not support enums with explicit discriminants");
+            }
+            &variant.ident
+        })
+        .collect();
+    let indices: Vec<usize> = (0..idents.len()).collect();
+    let count = idents.len();
+
+    let expanded = quote! {
+        impl #name {
+            /// Number of variants of this enum, i.e. the size of the domain
+            /// this type occupies when used as a `bit_set::bitidx::BitSet`
+            /// element.
+            pub const BIT_IDX_COUNT: usize = #count;
+        }
+
+        impl ::bit_set::bitidx::TryFromBitIdx for #name {
+            /// Reconstruct a variant from a `BitIdx`, returning `None`
+            /// rather than panicking if the index doesn't correspond to a
+            /// variant of this enum. Used by `BitSet::try_iter`.
+            fn try_from_bit_idx(idx: ::bit_set::bitidx::BitIdx) -> Option<Self> {
+                match idx.0 {
+                    #(#indices => Some(#name::#idents),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl ::std::convert::Into<::bit_set::bitidx::BitIdx> for #name {
+            #[inline]
+            fn into(self) -> ::bit_set::bitidx::BitIdx {
+                ::bit_set::bitidx::BitIdx(self as usize)
+            }
+        }
+
+        impl ::std::convert::From<::bit_set::bitidx::BitIdx> for #name {
+            fn from(idx: ::bit_set::bitidx::BitIdx) -> Self {
+                <#name as ::bit_set::bitidx::TryFromBitIdx>::try_from_bit_idx(idx)
+                    .unwrap_or_else(|| panic!("{} has no variant for {:?}", stringify!(#name), idx))
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
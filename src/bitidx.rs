@@ -7,6 +7,12 @@
 //!
 //! Aside from this, this `BitSet`'s API is identical to `bit_set::BitSet`.
 //!
+//! Writing `Into<BitIdx>`/`From<BitIdx>` by hand for a field-less enum is
+//! mostly boilerplate, and the `From<BitIdx>` side tends to end up as a
+//! `match` with a `panic!` fallback arm, as in the example below. The
+//! `bit-set-derive` crate's `#[derive(BitIdxEnum)]` generates both impls
+//! (plus a panic-free `try_from_bit_idx`) from the enum's variant list.
+//!
 //! # Example
 //!
 //! ```
@@ -41,13 +47,26 @@
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::fmt::{self, Debug};
+use std::ops::{BitAnd, BitOr, BitXor, Bound, RangeBounds, Sub};
 use bit_vec::{BitVec, BitBlock};
 
+pub mod growable;
+pub use self::growable::GrowableBitSet;
+
+pub mod hier;
+pub use self::hier::HierBitSet;
+
+pub mod atomic;
+pub use self::atomic::AtomicBitSet;
+
+pub mod bitmap;
+pub use self::bitmap::Bitmap;
+
 /// Wrapper for a bit index
 ///
 /// This is a simple wrapper for usize so that types can implement `Into`/`From`
 /// for BitIdx. Default implementation for `usize` so it works as normal.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct BitIdx(pub usize);
 
 impl Into<BitIdx> for usize {
@@ -66,6 +85,18 @@ impl AsRef<usize> for BitIdx {
     #[inline] fn as_ref(&self) -> &usize { &self.0 }
 }
 
+/// Fallible conversion from a `BitIdx`, used by [`BitSet::try_iter`].
+///
+/// This can't be `std::convert::TryFrom<BitIdx>`: any `T: From<BitIdx>`
+/// (required for `iter()`, `Debug`, etc.) already gets a blanket,
+/// infallible `TryFrom<BitIdx>` from std that just forwards to the
+/// (possibly panicking) `From::from`, which would make `try_iter` panic on
+/// exactly the out-of-range indices it exists to guard against.
+/// `#[derive(BitIdxEnum)]` implements this trait directly instead.
+pub trait TryFromBitIdx: Sized {
+    fn try_from_bit_idx(idx: BitIdx) -> Option<Self>;
+}
+
 /// A set of elements represented as a bit vector.
 ///
 /// Elements of the set are any type that implements `Into<BitIdx>` and
@@ -166,7 +197,7 @@ impl<T, B> BitSet<T, B>
     where T: From<BitIdx>, B: BitBlock
 {
     #[inline]
-    pub fn iter<'a>(&'a self) -> Iter<'a, T, B> {
+    pub fn iter(&self) -> Iter<'_, T, B> {
         MapBitIdx(self.0.iter(), PhantomData)
     }
 
@@ -189,6 +220,73 @@ impl<T, B> BitSet<T, B>
     pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T, B> {
         MapBitIdx(self.0.symmetric_difference(&other.0), PhantomData)
     }
+
+    /// The smallest element in the set, if any, in the style of `BTreeSet::first`.
+    #[inline]
+    pub fn first(&self) -> Option<T> { self.iter().next() }
+
+    /// The largest element in the set, if any, in the style of `BTreeSet::last`.
+    #[inline]
+    pub fn last(&self) -> Option<T> { self.iter().last() }
+
+    /// Iterate the elements whose underlying index falls within `range`, in
+    /// ascending order, in the style of `BTreeSet::range`.
+    #[inline]
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Range<'_, T, B> {
+        let start = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&e) => e + 1,
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => usize::max_value(),
+        };
+        Range { iter: self.0.iter(), start, end, _marker: PhantomData }
+    }
+}
+
+impl<T, B> BitSet<T, B>
+    where T: Into<BitIdx> + From<BitIdx>, B: BitBlock
+{
+    /// Return the set's own copy of `value` if it's present, in the style
+    /// of `BTreeSet::get`.
+    pub fn get(&self, value: T) -> Option<T> {
+        let BitIdx(idx) = value.into();
+        if self.0.contains(idx) {
+            Some(T::from(BitIdx(idx)))
+        } else {
+            None
+        }
+    }
+
+    /// Keep only the elements for which `f` returns `true`, in the style of
+    /// `BTreeSet::retain`.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let to_remove: Vec<BitIdx> = self.iter()
+            .filter(|item| !f(item))
+            .map(Into::into)
+            .collect();
+        for BitIdx(idx) in to_remove {
+            self.0.remove(idx);
+        }
+    }
+}
+
+impl<T, B> BitSet<T, B>
+    where T: TryFromBitIdx, B: BitBlock
+{
+    /// Iterate the set's elements, yielding `Err(idx)` for any backing bit
+    /// whose index doesn't correspond to a valid `T` rather than panicking.
+    ///
+    /// Useful when `T::from(BitIdx)` would otherwise have to panic on
+    /// out-of-range indices, e.g. when the backing `BitVec` may have been
+    /// deserialized and can't be trusted to only contain valid variants.
+    #[inline]
+    pub fn try_iter(&self) -> TryIter<'_, T, B> {
+        TryMapBitIdx(self.0.iter(), PhantomData)
+    }
 }
 
 impl<T, B> BitSet<T, B>
@@ -234,6 +332,56 @@ impl<I, T> Iterator for MapBitIdx<I, T>
     }
 }
 
+/// Iterator over the elements of a [`BitSet`] whose index falls within a
+/// given range, returned by [`BitSet::range`].
+pub struct Range<'a, T, B: BitBlock> {
+    iter: super::Iter<'a, B>,
+    start: usize,
+    end: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, B> Iterator for Range<'a, T, B>
+    where T: From<BitIdx>, B: BitBlock
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(idx) = self.iter.next() {
+            if idx < self.start {
+                continue;
+            }
+            if idx >= self.end {
+                return None;
+            }
+            return Some(T::from(BitIdx(idx)));
+        }
+        None
+    }
+}
+
+/// Like [`Iter`], but for element types whose conversion from a `BitIdx` can
+/// fail, such as an enum derived with `#[derive(BitIdxEnum)]`.
+pub type TryIter<'a, T, B> = TryMapBitIdx<super::Iter<'a, B>, T>;
+
+#[derive(Clone)]
+#[doc(hidden)]
+pub struct TryMapBitIdx<I, T>(I, PhantomData<T>);
+
+impl<I, T> Iterator for TryMapBitIdx<I, T>
+    where I: Iterator<Item=usize>, T: TryFromBitIdx
+{
+    type Item = Result<T, BitIdx>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|v| {
+            let idx = BitIdx(v);
+            T::try_from_bit_idx(idx).ok_or(idx)
+        })
+    }
+}
+
 impl<T, B: BitBlock> Default for BitSet<T, B> {
     fn default() -> Self { BitSet(Default::default(), PhantomData) }
 }
@@ -272,9 +420,49 @@ impl<T: From<BitIdx> + Debug, B: BitBlock> fmt::Debug for BitSet<T, B> {
     }
 }
 
+impl<'a, 'b, T, B: BitBlock> BitOr<&'b BitSet<T, B>> for &'a BitSet<T, B> {
+    type Output = BitSet<T, B>;
+
+    fn bitor(self, other: &'b BitSet<T, B>) -> BitSet<T, B> {
+        let mut result = BitSet(self.0.clone(), PhantomData);
+        result.0.union_with(&other.0);
+        result
+    }
+}
+
+impl<'a, 'b, T, B: BitBlock> BitAnd<&'b BitSet<T, B>> for &'a BitSet<T, B> {
+    type Output = BitSet<T, B>;
+
+    fn bitand(self, other: &'b BitSet<T, B>) -> BitSet<T, B> {
+        let mut result = BitSet(self.0.clone(), PhantomData);
+        result.0.intersect_with(&other.0);
+        result
+    }
+}
+
+impl<'a, 'b, T, B: BitBlock> Sub<&'b BitSet<T, B>> for &'a BitSet<T, B> {
+    type Output = BitSet<T, B>;
+
+    fn sub(self, other: &'b BitSet<T, B>) -> BitSet<T, B> {
+        let mut result = BitSet(self.0.clone(), PhantomData);
+        result.0.difference_with(&other.0);
+        result
+    }
+}
+
+impl<'a, 'b, T, B: BitBlock> BitXor<&'b BitSet<T, B>> for &'a BitSet<T, B> {
+    type Output = BitSet<T, B>;
+
+    fn bitxor(self, other: &'b BitSet<T, B>) -> BitSet<T, B> {
+        let mut result = BitSet(self.0.clone(), PhantomData);
+        result.0.symmetric_difference_with(&other.0);
+        result
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{BitSet, BitIdx};
+    use super::{BitSet, BitIdx, TryFromBitIdx};
 
     #[derive(Debug, PartialEq)]
     enum Foo { A, B, C, D }
@@ -295,6 +483,18 @@ mod test {
         }
     }
 
+    impl TryFromBitIdx for Foo {
+        fn try_from_bit_idx(BitIdx(v): BitIdx) -> Option<Self> {
+            match v {
+                0 => Some(Foo::A),
+                1 => Some(Foo::B),
+                2 => Some(Foo::C),
+                3 => Some(Foo::D),
+                _ => None,
+            }
+        }
+    }
+
     #[test]
     fn iter() {
         let mut s = BitSet::new();
@@ -305,4 +505,57 @@ mod test {
         let v: Vec<_> = s.iter().collect();
         assert_eq!(v, vec![Foo::A, Foo::C]);
     }
+
+    #[test]
+    fn try_iter_reports_bad_indices() {
+        let mut s: BitSet<Foo> = BitSet::from_bit_vec(::bit_vec::BitVec::from_elem(6, false));
+
+        s.insert(Foo::A);
+        s.0.set(4, true);
+
+        let v: Vec<_> = s.try_iter().collect();
+        assert_eq!(v, vec![Ok(Foo::A), Err(BitIdx(4))]);
+    }
+
+    #[test]
+    fn first_last_and_range() {
+        let mut s = BitSet::new();
+        s.insert(Foo::B);
+        s.insert(Foo::C);
+        s.insert(Foo::D);
+
+        assert_eq!(s.first(), Some(Foo::B));
+        assert_eq!(s.last(), Some(Foo::D));
+        assert_eq!(s.range(2..4).collect::<Vec<_>>(), vec![Foo::C, Foo::D]);
+    }
+
+    #[test]
+    fn get_and_retain() {
+        let mut s = BitSet::new();
+        s.insert(Foo::A);
+        s.insert(Foo::B);
+        s.insert(Foo::C);
+
+        assert_eq!(s.get(Foo::B), Some(Foo::B));
+        assert_eq!(s.get(Foo::D), None);
+
+        s.retain(|f| *f != Foo::B);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![Foo::A, Foo::C]);
+    }
+
+    #[test]
+    fn set_operators_return_fresh_sets() {
+        let mut a = BitSet::new();
+        a.insert(Foo::A);
+        a.insert(Foo::B);
+
+        let mut b = BitSet::new();
+        b.insert(Foo::B);
+        b.insert(Foo::C);
+
+        assert_eq!((&a | &b).iter().collect::<Vec<_>>(), vec![Foo::A, Foo::B, Foo::C]);
+        assert_eq!((&a & &b).iter().collect::<Vec<_>>(), vec![Foo::B]);
+        assert_eq!((&a - &b).iter().collect::<Vec<_>>(), vec![Foo::A]);
+        assert_eq!((&a ^ &b).iter().collect::<Vec<_>>(), vec![Foo::A, Foo::C]);
+    }
 }
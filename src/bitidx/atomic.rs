@@ -0,0 +1,118 @@
+//! A fixed-capacity typed bit set that can be mutated through `&self`
+//!
+//! `AtomicBitSet` stores its bits in `AtomicUsize` words so that multiple
+//! threads can flip bits in a shared set without any external locking —
+//! the common ECS pattern of many worker threads each marking which typed
+//! entities are "dirty", followed by a single serial collection pass. This
+//! mirrors `hibitset`'s `AtomicBitSet`, minus its layered summary index;
+//! [`HierBitSet`](super::HierBitSet) is the place to add that once there's
+//! a concurrent consumer that needs fast sparse iteration rather than just
+//! fast concurrent insert/remove/contains.
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use super::{BitIdx, BitSet};
+
+const BITS: usize = usize::BITS as usize;
+
+/// A `BitSet` over a fixed domain whose bits live in atomics, so `insert`,
+/// `remove` and `contains` only need `&self`.
+///
+/// Unlike [`super::BitSet`], the domain size is fixed at construction —
+/// growing the backing storage isn't safe to do through a shared
+/// reference, so out-of-range indices panic instead.
+pub struct AtomicBitSet<T> {
+    words: Vec<AtomicUsize>,
+    domain_size: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> AtomicBitSet<T> {
+    pub fn with_capacity(domain_size: usize) -> Self {
+        let nwords = (domain_size + BITS - 1) / BITS;
+        AtomicBitSet {
+            words: (0..nwords).map(|_| AtomicUsize::new(0)).collect(),
+            domain_size,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn domain_size(&self) -> usize { self.domain_size }
+
+    fn word_and_mask(&self, idx: usize) -> (usize, usize) {
+        assert!(idx < self.domain_size,
+            "index {} is out of domain (domain size {})", idx, self.domain_size);
+        (idx / BITS, 1usize << (idx % BITS))
+    }
+
+    /// Atomically consume every set bit, clearing this set and handing the
+    /// result to a single owner as a plain, non-atomic `BitSet`.
+    pub fn drain(&self) -> BitSet<T, u32> {
+        let mut out = BitSet::with_capacity(self.domain_size);
+        for (word_idx, word) in self.words.iter().enumerate() {
+            let bits = word.swap(0, Ordering::SeqCst);
+            if bits == 0 {
+                continue;
+            }
+            for bit in 0..BITS {
+                if bits & (1 << bit) != 0 {
+                    out.0.insert(word_idx * BITS + bit);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<T> AtomicBitSet<T>
+    where T: Into<BitIdx>
+{
+    /// Set the bit for `value`, returning whether it was previously unset.
+    pub fn insert(&self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        let (word, mask) = self.word_and_mask(idx);
+        self.words[word].fetch_or(mask, Ordering::SeqCst) & mask == 0
+    }
+
+    /// Clear the bit for `value`, returning whether it was previously set.
+    pub fn remove(&self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        let (word, mask) = self.word_and_mask(idx);
+        self.words[word].fetch_and(!mask, Ordering::SeqCst) & mask != 0
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        let (word, mask) = self.word_and_mask(idx);
+        self.words[word].load(Ordering::SeqCst) & mask != 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AtomicBitSet;
+
+    #[test]
+    fn insert_contains_remove() {
+        let s: AtomicBitSet<usize> = AtomicBitSet::with_capacity(128);
+
+        assert!(s.insert(42usize));
+        assert!(!s.insert(42usize));
+        assert!(s.contains(42usize));
+        assert!(s.remove(42usize));
+        assert!(!s.contains(42usize));
+    }
+
+    #[test]
+    fn drain_consumes_into_plain_bit_set() {
+        let s: AtomicBitSet<usize> = AtomicBitSet::with_capacity(128);
+        s.insert(1usize);
+        s.insert(100usize);
+
+        let drained = s.drain();
+        assert!(drained.contains(1usize));
+        assert!(drained.contains(100usize));
+        assert!(!s.contains(1usize));
+        assert!(!s.contains(100usize));
+    }
+}
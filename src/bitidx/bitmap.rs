@@ -0,0 +1,161 @@
+//! A 2D monochrome raster mask built on `BitSet`
+//!
+//! `Bitmap` maps `(x, y)` coordinates onto a single linear `BitIdx`
+//! (`y * width + x`) and stores them in a [`super::BitSet<usize, B>`].
+//! Because the mapping is just a `usize`, every existing set operation —
+//! `union_with` to blit two bitmaps together, `intersect_with` to mask one
+//! against another, `Debug` to dump the packed bits — comes for free.
+//! Useful for glyph bitmaps (e.g. BDF fonts) or any other small monochrome
+//! raster.
+use bit_vec::BitBlock;
+use super::BitSet;
+
+/// A fixed-size 2D bitmap, backed by a packed [`BitSet`].
+#[derive(Clone, Debug)]
+pub struct Bitmap<B = u32>
+    where B: BitBlock
+{
+    bits: BitSet<usize, B>,
+    width: usize,
+    height: usize,
+}
+
+impl Bitmap<u32> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Bitmap {
+            bits: BitSet::with_capacity(width * height),
+            width,
+            height,
+        }
+    }
+}
+
+impl<B: BitBlock> Bitmap<B> {
+    #[inline]
+    pub fn width(&self) -> usize { self.width }
+
+    #[inline]
+    pub fn height(&self) -> usize { self.height }
+
+    fn idx(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height,
+            "coordinate ({}, {}) out of bounds for a {}x{} Bitmap", x, y, self.width, self.height);
+        y * self.width + x
+    }
+
+    #[inline]
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.bits.contains(self.idx(x, y))
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        let idx = self.idx(x, y);
+        if value {
+            self.bits.insert(idx);
+        } else {
+            self.bits.remove(idx);
+        }
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        debug_assert_eq!((self.width, self.height), (other.width, other.height),
+            "cannot union Bitmaps of different dimensions");
+        self.bits.union_with(&other.bits)
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        debug_assert_eq!((self.width, self.height), (other.width, other.height),
+            "cannot intersect Bitmaps of different dimensions");
+        self.bits.intersect_with(&other.bits)
+    }
+
+    /// Iterate the cells of row `y`, left to right.
+    pub fn row(&self, y: usize) -> Row<'_, B> {
+        Row { bitmap: self, y, x: 0 }
+    }
+
+    /// Iterate the cells of column `x`, top to bottom.
+    pub fn column(&self, x: usize) -> Column<'_, B> {
+        Column { bitmap: self, x, y: 0 }
+    }
+}
+
+/// Iterator over one row of a [`Bitmap`], returned by [`Bitmap::row`].
+pub struct Row<'a, B: BitBlock> {
+    bitmap: &'a Bitmap<B>,
+    y: usize,
+    x: usize,
+}
+
+impl<'a, B: BitBlock> Iterator for Row<'a, B> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.x >= self.bitmap.width {
+            return None;
+        }
+        let value = self.bitmap.get(self.x, self.y);
+        self.x += 1;
+        Some(value)
+    }
+}
+
+/// Iterator over one column of a [`Bitmap`], returned by [`Bitmap::column`].
+pub struct Column<'a, B: BitBlock> {
+    bitmap: &'a Bitmap<B>,
+    x: usize,
+    y: usize,
+}
+
+impl<'a, B: BitBlock> Iterator for Column<'a, B> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.y >= self.bitmap.height {
+            return None;
+        }
+        let value = self.bitmap.get(self.x, self.y);
+        self.y += 1;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bitmap;
+
+    #[test]
+    fn get_set_round_trip() {
+        let mut b = Bitmap::new(4, 4);
+
+        assert!(!b.get(1, 2));
+        b.set(1, 2, true);
+        assert!(b.get(1, 2));
+
+        b.set(1, 2, false);
+        assert!(!b.get(1, 2));
+    }
+
+    #[test]
+    fn union_blits_one_bitmap_onto_another() {
+        let mut a = Bitmap::new(2, 2);
+        let mut b = Bitmap::new(2, 2);
+
+        a.set(0, 0, true);
+        b.set(1, 1, true);
+        a.union_with(&b);
+
+        assert!(a.get(0, 0));
+        assert!(a.get(1, 1));
+    }
+
+    #[test]
+    fn row_and_column_iterate_in_order() {
+        let mut b = Bitmap::new(3, 2);
+        b.set(0, 1, true);
+        b.set(2, 1, true);
+
+        assert_eq!(b.row(1).collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(b.column(2).collect::<Vec<_>>(), vec![false, true]);
+    }
+}
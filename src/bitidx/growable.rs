@@ -0,0 +1,133 @@
+//! A typed bit set with an explicit, separately-tracked domain size
+//!
+//! `super::BitSet` grows its backing storage implicitly on every `insert`,
+//! which is convenient but means an out-of-domain index (e.g. a stale
+//! serialized set reused after an enum gained a variant) is silently
+//! accepted rather than rejected. `GrowableBitSet` instead tracks a
+//! `domain_size` up front, debug-asserts that every `insert`/`contains`/
+//! `remove` stays within it, and only changes the domain when asked to via
+//! [`GrowableBitSet::ensure`] or [`GrowableBitSet::grow_to`] — mirroring the
+//! split rustc's dataflow code makes between a fixed-domain `BitSet` and a
+//! `GrowableBitSet`.
+use bit_vec::BitBlock;
+use super::{BitIdx, BitSet};
+
+/// A `BitSet` over a fixed, explicitly-tracked logical domain.
+///
+/// Unlike [`BitSet`], which grows to fit whatever is inserted,
+/// `GrowableBitSet` treats its `domain_size` as a contract: `insert`,
+/// `contains` and `remove` debug-assert the index they're given is within
+/// the domain, and the domain itself is only changed by [`ensure`] or
+/// [`grow_to`].
+///
+/// [`ensure`]: GrowableBitSet::ensure
+/// [`grow_to`]: GrowableBitSet::grow_to
+#[derive(Clone)]
+pub struct GrowableBitSet<T, B = u32>
+    where B: BitBlock
+{
+    set: BitSet<T, B>,
+    domain_size: usize,
+}
+
+impl<T> GrowableBitSet<T, u32> {
+    #[inline]
+    pub fn new() -> Self { Self::with_capacity(0) }
+
+    #[inline]
+    pub fn with_capacity(domain_size: usize) -> Self {
+        GrowableBitSet { set: BitSet::with_capacity(domain_size), domain_size }
+    }
+}
+
+impl<T, B: BitBlock> GrowableBitSet<T, B> {
+    /// The current size of the domain; every in-domain index is `< domain_size()`.
+    #[inline]
+    pub fn domain_size(&self) -> usize { self.domain_size }
+
+    #[inline]
+    pub fn len(&self) -> usize { self.set.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.set.is_empty() }
+
+    /// Grow the domain to `domain_size`, extending the backing storage to
+    /// match. A no-op if `domain_size` is not larger than the current one.
+    pub fn grow_to(&mut self, domain_size: usize) {
+        if domain_size > self.domain_size {
+            self.set.reserve_len(domain_size);
+            self.domain_size = domain_size;
+        }
+    }
+
+    /// Grow the domain, if necessary, so that `idx` is in range.
+    #[inline]
+    pub fn ensure(&mut self, idx: BitIdx) {
+        let BitIdx(idx) = idx;
+        if idx >= self.domain_size {
+            self.grow_to(idx + 1);
+        }
+    }
+}
+
+impl<T, B> GrowableBitSet<T, B>
+    where T: Into<BitIdx>, B: BitBlock
+{
+    /// Insert `value`, panicking in debug builds if it falls outside the
+    /// current domain. Use [`ensure`](Self::ensure) first to grow the
+    /// domain on demand instead.
+    pub fn insert(&mut self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        debug_assert!(idx < self.domain_size,
+            "index {} is out of domain (domain size {})", idx, self.domain_size);
+        self.set.0.insert(idx)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        debug_assert!(idx < self.domain_size,
+            "index {} is out of domain (domain size {})", idx, self.domain_size);
+        self.set.0.contains(idx)
+    }
+
+    pub fn remove(&mut self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        debug_assert!(idx < self.domain_size,
+            "index {} is out of domain (domain size {})", idx, self.domain_size);
+        self.set.0.remove(idx)
+    }
+}
+
+impl<T, B> GrowableBitSet<T, B>
+    where T: From<BitIdx>, B: BitBlock
+{
+    #[inline]
+    pub fn iter(&self) -> super::Iter<'_, T, B> { self.set.iter() }
+}
+
+impl<T, B: BitBlock> Default for GrowableBitSet<T, B> {
+    fn default() -> Self {
+        GrowableBitSet { set: Default::default(), domain_size: 0 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GrowableBitSet;
+
+    #[test]
+    #[should_panic(expected = "out of domain")]
+    fn insert_out_of_domain_panics_in_debug() {
+        let mut s: GrowableBitSet<usize> = GrowableBitSet::with_capacity(4);
+        s.insert(10);
+    }
+
+    #[test]
+    fn ensure_grows_domain() {
+        let mut s: GrowableBitSet<usize> = GrowableBitSet::with_capacity(4);
+        s.ensure(10usize.into());
+        assert!(s.domain_size() > 10);
+        assert!(s.insert(10));
+        assert!(s.contains(10));
+    }
+}
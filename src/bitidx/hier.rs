@@ -0,0 +1,348 @@
+//! A typed bit set with a hierarchical summary index for fast iteration
+//! over large, sparse domains.
+//!
+//! Plain `super::BitSet` iteration scans every bit (one word at a time) in
+//! the backing `BitVec`, which is fine for small or dense sets but wasteful
+//! for something like an entity-ID domain where only a handful of bits out
+//! of millions are ever set. `HierBitSet` keeps a small tower of summary
+//! `BitVec`s on top of the real bits, in the style of `hibitset`: a set bit
+//! in level `N + 1` means "some bit in the corresponding group of level `N`
+//! is set", so iteration can skip whole empty groups instead of visiting
+//! every bit in them.
+use std::marker::PhantomData;
+use bit_vec::{BitVec, BitBlock};
+use super::BitIdx;
+
+/// A `BitSet` with a hierarchical summary index, trading a little memory
+/// and update overhead for faster iteration over large, sparse domains.
+///
+/// Kept as a separate type from [`super::BitSet`] so the plain set stays
+/// allocation-minimal; reach for `HierBitSet` when the domain is large and
+/// mostly empty.
+pub struct HierBitSet<T, B = u32>
+    where B: BitBlock
+{
+    bits: BitVec<B>,
+    /// `levels[0]` summarizes `bits` (one bit per group of `group_size`
+    /// bits of `bits`); each subsequent level summarizes the one below it
+    /// the same way. The last level is the root of the tower.
+    levels: Vec<BitVec<B>>,
+    group_size: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> HierBitSet<T, u32> {
+    #[inline]
+    pub fn new() -> Self { Self::with_capacity(0) }
+
+    pub fn with_capacity(domain_size: usize) -> Self {
+        let mut set = HierBitSet {
+            bits: BitVec::from_elem(domain_size, false),
+            levels: Vec::new(),
+            group_size: <u32 as BitBlock>::bits(),
+            _marker: PhantomData,
+        };
+        set.rebuild_levels();
+        set
+    }
+}
+
+impl<T, B: BitBlock> HierBitSet<T, B> {
+    #[inline]
+    pub fn domain_size(&self) -> usize { self.bits.len() }
+
+    #[inline]
+    pub fn len(&self) -> usize { self.bits.iter().filter(|&b| b).count() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.levels.last().map_or_else(|| self.bits.none(), |top| top.none())
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+        for level in &mut self.levels {
+            level.clear();
+        }
+    }
+
+    /// Grow the domain so that `domain_size` bits are addressable.
+    pub fn grow_to(&mut self, domain_size: usize) {
+        if domain_size > self.bits.len() {
+            self.bits.grow(domain_size - self.bits.len(), false);
+            self.rebuild_levels();
+        }
+    }
+
+    /// Rebuild every summary level from `bits` from scratch. Used after
+    /// growing the domain or after a bulk operation touches many bits at
+    /// once, where recomputing is simpler (and no slower) than threading
+    /// incremental updates through the op.
+    fn rebuild_levels(&mut self) {
+        self.levels.clear();
+        let group_size = self.group_size;
+        let mut len = self.bits.len();
+        let mut level_idx = 0;
+        while len > group_size {
+            let level_len = (len + group_size - 1) / group_size;
+            // `BitVec::<B>::from_elem` only exists for `BitVec<u32>`, so
+            // build the (all-`false`) level generically via `grow` instead,
+            // the same way `grow_to` extends `bits` for any `B`.
+            let mut level = BitVec::<B>::default();
+            level.grow(level_len, false);
+            for group in 0..level_len {
+                let start = group * group_size;
+                let end = (start + group_size).min(len);
+                let any_set = if level_idx == 0 {
+                    (start..end).any(|i| self.bits.get(i).unwrap_or(false))
+                } else {
+                    let prev = &self.levels[level_idx - 1];
+                    (start..end).any(|i| prev.get(i).unwrap_or(false))
+                };
+                level.set(group, any_set);
+            }
+            self.levels.push(level);
+            len = level_len;
+            level_idx += 1;
+        }
+    }
+
+    /// A copy of `bits`, padded with `false` or truncated to exactly `len`
+    /// entries. Used to make `union`/`intersect` operands the same length,
+    /// which `bit_vec::BitVec::union`/`intersect` otherwise panic on.
+    fn resized_bits(bits: &BitVec<B>, len: usize) -> BitVec<B> {
+        let mut out = BitVec::<B>::default();
+        for i in 0..len {
+            out.push(bits.get(i).unwrap_or(false));
+        }
+        out
+    }
+
+    /// Mark every group containing `idx` as non-empty, up through the tower.
+    fn mark_set(&mut self, idx: usize) {
+        let mut group_size = self.group_size;
+        for level in &mut self.levels {
+            let group = idx / group_size;
+            level.set(group, true);
+            group_size *= self.group_size;
+        }
+    }
+
+    /// Re-check whether the groups containing `idx` are still non-empty,
+    /// clearing their summary bit if not. Stops as soon as a level is found
+    /// to still have a set bit, since higher levels are then unaffected.
+    fn recheck_clear(&mut self, idx: usize) {
+        // `cumulative` is the number of original bits a group at the
+        // current level spans (`group_size^(level_idx + 1)`), used to find
+        // which group `idx` falls in. The range re-checked within that
+        // group, however, is always `group_size` entries of the *previous*
+        // level (or of `bits`, for level 0) — not `cumulative` of them.
+        let mut cumulative = self.group_size;
+        for level_idx in 0..self.levels.len() {
+            let group = idx / cumulative;
+            let start = group * self.group_size;
+            let end = (start + self.group_size).min(if level_idx == 0 { self.bits.len() } else { self.levels[level_idx - 1].len() });
+            let still_set = if level_idx == 0 {
+                (start..end).any(|i| self.bits.get(i).unwrap_or(false))
+            } else {
+                (start..end).any(|i| self.levels[level_idx - 1].get(i).unwrap_or(false))
+            };
+            if still_set {
+                break;
+            }
+            self.levels[level_idx].set(group, false);
+            cumulative *= self.group_size;
+        }
+    }
+}
+
+impl<T, B> HierBitSet<T, B>
+    where T: Into<BitIdx>, B: BitBlock
+{
+    pub fn insert(&mut self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        if idx >= self.bits.len() {
+            self.grow_to(idx + 1);
+        }
+        let was_set = self.bits.get(idx).unwrap_or(false);
+        if !was_set {
+            self.bits.set(idx, true);
+            self.mark_set(idx);
+        }
+        !was_set
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        self.bits.get(idx).unwrap_or(false)
+    }
+
+    pub fn remove(&mut self, value: T) -> bool {
+        let BitIdx(idx) = value.into();
+        let was_set = self.bits.get(idx).unwrap_or(false);
+        if was_set {
+            self.bits.set(idx, false);
+            self.recheck_clear(idx);
+        }
+        was_set
+    }
+
+    /// OR `other`'s bits into `self`, growing `self`'s domain to fit if
+    /// `other`'s is larger.
+    pub fn union_with(&mut self, other: &Self) {
+        self.grow_to(other.bits.len());
+        let other_bits = Self::resized_bits(&other.bits, self.bits.len());
+        self.bits.union(&other_bits);
+        self.rebuild_levels();
+    }
+
+    /// AND `other`'s bits into `self`. `self`'s domain is never grown;
+    /// indices outside `other`'s domain are treated as absent from it, so
+    /// they're simply cleared from `self`.
+    pub fn intersect_with(&mut self, other: &Self) {
+        let other_bits = Self::resized_bits(&other.bits, self.bits.len());
+        self.bits.intersect(&other_bits);
+        self.rebuild_levels();
+    }
+}
+
+impl<T, B> HierBitSet<T, B>
+    where T: From<BitIdx>, B: BitBlock
+{
+    /// Iterate the set's elements, using the summary tower to skip entirely
+    /// empty groups instead of visiting every bit.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, B> {
+        Iter { set: self, pos: 0, _marker: PhantomData }
+    }
+}
+
+impl<T, B: BitBlock> Default for HierBitSet<T, B> {
+    fn default() -> Self {
+        let mut set = HierBitSet {
+            bits: Default::default(),
+            levels: Vec::new(),
+            group_size: B::bits(),
+            _marker: PhantomData,
+        };
+        set.rebuild_levels();
+        set
+    }
+}
+
+/// Iterator over the elements of a [`HierBitSet`].
+pub struct Iter<'a, T, B: BitBlock> {
+    set: &'a HierBitSet<T, B>,
+    pos: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, B> Iterator for Iter<'a, T, B>
+    where T: From<BitIdx>, B: BitBlock
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let domain_size = self.set.bits.len();
+        'outer: while self.pos < domain_size {
+            if self.set.bits.get(self.pos).unwrap_or(false) {
+                let idx = self.pos;
+                self.pos += 1;
+                return Some(T::from(BitIdx(idx)));
+            }
+
+            // Try to skip the rest of the lowest-level group `pos` falls
+            // in, if the summary says it's entirely empty.
+            let mut group_size = self.set.group_size;
+            for level in &self.set.levels {
+                let group = self.pos / group_size;
+                if !level.get(group).unwrap_or(true) {
+                    self.pos = (group + 1) * group_size;
+                    continue 'outer;
+                }
+                group_size *= self.set.group_size;
+            }
+            self.pos += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HierBitSet;
+
+    #[test]
+    fn sparse_iter_skips_empty_groups() {
+        let mut s: HierBitSet<usize> = HierBitSet::with_capacity(10_000);
+
+        s.insert(3usize);
+        s.insert(9_000usize);
+
+        let v: Vec<usize> = s.iter().collect();
+        assert_eq!(v, vec![3, 9_000]);
+        assert_eq!(s.len(), 2);
+    }
+
+    #[test]
+    fn remove_clears_summary() {
+        let mut s: HierBitSet<usize> = HierBitSet::with_capacity(10_000);
+
+        s.insert(42usize);
+        assert!(s.remove(42usize));
+        assert!(s.is_empty());
+        assert_eq!(s.iter().collect::<Vec<usize>>(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn remove_preserves_sibling_in_same_higher_level_group() {
+        let mut s: HierBitSet<usize> = HierBitSet::with_capacity(10_000);
+
+        // 8200 and 9200 land in different level-0 groups but the same
+        // level-1 group, so removing one must not clear the level-1
+        // summary bit the other still depends on.
+        s.insert(8_200usize);
+        s.insert(9_200usize);
+        assert!(s.remove(8_200usize));
+
+        assert!(s.contains(9_200usize));
+        assert_eq!(s.iter().collect::<Vec<usize>>(), vec![9_200]);
+    }
+
+    #[test]
+    fn union_with_larger_self_does_not_panic() {
+        let mut big: HierBitSet<usize> = HierBitSet::with_capacity(10_000);
+        big.insert(5usize);
+        let mut small: HierBitSet<usize> = HierBitSet::with_capacity(100);
+        small.insert(42usize);
+
+        big.union_with(&small);
+
+        assert_eq!(big.iter().collect::<Vec<usize>>(), vec![5, 42]);
+    }
+
+    #[test]
+    fn union_with_larger_other_grows_self() {
+        let mut small: HierBitSet<usize> = HierBitSet::with_capacity(100);
+        small.insert(5usize);
+        let mut big: HierBitSet<usize> = HierBitSet::with_capacity(10_000);
+        big.insert(9_000usize);
+
+        small.union_with(&big);
+
+        assert_eq!(small.iter().collect::<Vec<usize>>(), vec![5, 9_000]);
+    }
+
+    #[test]
+    fn intersect_with_differently_sized_operand_does_not_panic() {
+        let mut big: HierBitSet<usize> = HierBitSet::with_capacity(10_000);
+        big.insert(5usize);
+        big.insert(9_000usize);
+        let mut small: HierBitSet<usize> = HierBitSet::with_capacity(100);
+        small.insert(5usize);
+
+        big.intersect_with(&small);
+
+        assert_eq!(big.iter().collect::<Vec<usize>>(), vec![5]);
+    }
+}